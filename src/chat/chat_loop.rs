@@ -2,8 +2,12 @@ use std::error::Error;
 use std::io::{self, Write};
 
 use crate::{
-    chat::{git, tool_checker::run_tools},
+    chat::{
+        git,
+        tool_checker::{run_tool_calls, run_tools},
+    },
     llm_api::{LLMClient, Message, Role},
+    tools::{get_tool_prompt, ToolConfig},
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -17,6 +21,7 @@ pub struct ChatLoop {
     llm_api: Box<dyn LLMClient>,
     system_prompt: String,
     conversation_history: Vec<Message>,
+    tool_config: ToolConfig,
 }
 
 impl ChatLoop {
@@ -27,13 +32,29 @@ impl ChatLoop {
             llm_api,
             system_prompt,
             conversation_history,
+            tool_config: ToolConfig::default(),
         }
     }
 
+    pub fn with_tool_config(mut self, tool_config: ToolConfig) -> Self {
+        self.tool_config = tool_config;
+        self
+    }
+
     pub fn get_conversation_history(&self) -> Vec<Message> {
         self.conversation_history.clone()
     }
 
+    /// The system prompt sent with each request. Clients without native tool
+    /// calling get the fenced block tool descriptions appended.
+    fn request_system_prompt(&self) -> String {
+        if self.llm_api.supports_native_tools() {
+            self.system_prompt.clone()
+        } else {
+            format!("{}{}", self.system_prompt, get_tool_prompt())
+        }
+    }
+
     pub async fn run(&mut self) -> Result<(), Box<dyn Error>> {
         // Check for uncommitted git changes
         if let Some((has_changes, files)) = git::has_uncommitted_changes() {
@@ -78,7 +99,7 @@ impl ChatLoop {
         }
 
         let mut loop_status = LoopStatus::UserInput;
-        let mut tool_input = String::new();
+        let mut tool_input = Vec::new();
 
         while loop_status != LoopStatus::Exit && loop_status != LoopStatus::Error {
             let input = if loop_status == LoopStatus::UserInput {
@@ -97,36 +118,52 @@ impl ChatLoop {
                     loop_status = LoopStatus::Exit;
                     continue;
                 }
-                format!("Help me with my task. {}\nKeep in mind to use the tools described in the system prompt", user_input.trim())
+                vec![Message::new(
+                    Role::User,
+                    format!("Help me with my task. {}\nKeep in mind to use the tools described in the system prompt", user_input.trim()),
+                )]
             } else {
-                tool_input.clone()
+                std::mem::take(&mut tool_input)
             };
 
             println!("Thinking...");
 
-            // Get response from LLM
-            match self
+            let mut messages = self.conversation_history.clone();
+            messages.extend(input.iter().cloned());
+
+            print!("/ASSISTANT/ ");
+            io::stdout().flush()?;
+
+            // Get response from LLM, printing the tokens as they arrive
+            let result = self
                 .llm_api
-                .chat(
-                    Some(self.system_prompt.clone()),
-                    self.conversation_history.clone(),
-                    input.to_string(),
-                )
-                .await
-            {
+                .chat_stream(Some(self.request_system_prompt()), messages, &mut |delta| {
+                    print!("{}", delta);
+                    let _ = io::stdout().flush();
+                })
+                .await;
+            println!();
+
+            match result {
                 Ok(response) => {
-                    println!("/ASSISTANT/ {}", response.content);
+                    for call in &response.tool_calls {
+                        println!("/TOOL/ {} {}", call.name, call.arguments);
+                    }
                     let response_str = response.content.clone();
-                    self.conversation_history.push(Message {
-                        role: Role::User,
-                        content: input.to_string(),
-                    });
+                    let tool_calls = response.tool_calls.clone();
+                    self.conversation_history.extend(input);
                     self.conversation_history.push(response);
 
-                    match run_tools(&response_str)? {
+                    if !tool_calls.is_empty() {
+                        tool_input = run_tool_calls(&tool_calls, &self.tool_config)?;
+                        loop_status = LoopStatus::ToolInput;
+                        continue;
+                    }
+
+                    match run_tools(&response_str, &self.tool_config)? {
                         None => loop_status = LoopStatus::UserInput,
                         Some(tool_output) => {
-                            tool_input = tool_output;
+                            tool_input = vec![Message::new(Role::User, tool_output)];
                             loop_status = LoopStatus::ToolInput;
                         }
                     }
@@ -176,10 +213,7 @@ mod tests {
             }
 
             let response = responses.remove(0);
-            Ok(Message {
-                role: Role::Agent,
-                content: response,
-            })
+            Ok(Message::new(Role::Agent, response))
         }
     }
 
@@ -192,4 +226,17 @@ mod tests {
         // Test that the initial conversation history contains the system prompt
         assert_eq!(chat_loop.conversation_history.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_tool_prompt_only_without_native_tools() {
+        let mock_api = Box::new(MockLLMAPI::new(vec![]));
+        let chat_loop = ChatLoop::new(mock_api, "Test system prompt".to_string());
+
+        assert!(chat_loop
+            .request_system_prompt()
+            .starts_with("Test system prompt"));
+        assert!(chat_loop
+            .request_system_prompt()
+            .contains("# List of tools provided"));
+    }
 }
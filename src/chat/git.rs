@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::process::Command;
 
 /// Checks if the current directory is a git repository and if it has uncommitted changes
@@ -35,6 +36,26 @@ pub fn has_uncommitted_changes() -> Option<(bool, Vec<String>)> {
     Some((!files.is_empty(), files))
 }
 
+/// Lists the files below `dir` that are tracked or not ignored by git
+///
+/// Returns `None` if `dir` is not inside a git repository. The paths are
+/// relative to `dir`.
+pub fn list_files(dir: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["ls-files", "--cached", "--others", "--exclude-standard"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    Some(output.lines().map(|line| line.to_string()).collect())
+}
+
 /// Commits all changes in the repository with the given commit message
 ///
 /// # Arguments
@@ -1,5 +1,5 @@
 mod chat_loop;
-mod git;
+pub(crate) mod git;
 mod prompt;
 mod tool_checker;
 
@@ -1,4 +1,5 @@
-use crate::tools::{get_all_tools, Tool};
+use crate::llm_api::{Message, ToolCall};
+use crate::tools::{get_configured_tools, Tool, ToolConfig};
 use std::cell::RefCell;
 use std::error::Error;
 use std::rc::Rc;
@@ -10,9 +11,9 @@ pub struct ToolInput {
     pub content: String,
 }
 
-pub fn run_tools(llm_output: &str) -> Result<Option<String>, Box<dyn Error>> {
+pub fn run_tools(llm_output: &str, config: &ToolConfig) -> Result<Option<String>, Box<dyn Error>> {
     let tool_candidates = check_for_tools(llm_output);
-    let all_tools = get_all_tools();
+    let all_tools = get_configured_tools(config);
 
     execute_tools(tool_candidates, all_tools)
 }
@@ -35,6 +36,38 @@ fn execute_tools(
     Ok(None)
 }
 
+/// Executes native function calls and answers each of them with a tool message
+pub fn run_tool_calls(
+    tool_calls: &[ToolCall],
+    config: &ToolConfig,
+) -> Result<Vec<Message>, Box<dyn Error>> {
+    execute_tool_calls(tool_calls, get_configured_tools(config))
+}
+
+fn execute_tool_calls(
+    tool_calls: &[ToolCall],
+    all_tools: Vec<Box<dyn Tool>>,
+) -> Result<Vec<Message>, Box<dyn Error>> {
+    let mut results = Vec::new();
+
+    for call in tool_calls {
+        let content = match all_tools
+            .iter()
+            .find(|tool| tool.get_indicator() == call.name)
+        {
+            Some(tool) => {
+                let (parameter, content) = tool.parse_arguments(&call.arguments);
+                tool.execute(parameter.as_deref(), &content)?
+                    .unwrap_or_else(|| "Done.".to_string())
+            }
+            None => format!("error: unknown tool `{}`", call.name),
+        };
+        results.push(Message::tool_result(call.id.clone(), content));
+    }
+
+    Ok(results)
+}
+
 fn check_for_tools(llm_output: &str) -> Vec<ToolInput> {
     let mut tool_inputs = Vec::new();
     let mut current_pos = 0;
@@ -78,6 +111,8 @@ fn check_for_tools(llm_output: &str) -> Vec<ToolInput> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm_api::Role;
+    use serde_json::{json, Value};
 
     #[derive(Clone)]
     struct MockTool {
@@ -109,6 +144,18 @@ mod tests {
             self.indicator.clone()
         }
 
+        fn get_summary(&self) -> String {
+            "Mock tool".to_string()
+        }
+
+        fn get_parameters(&self) -> Value {
+            json!({"type": "object", "properties": {}})
+        }
+
+        fn parse_arguments(&self, arguments: &Value) -> (Option<String>, String) {
+            (None, arguments.to_string())
+        }
+
         fn execute(
             &self,
             _parameter: Option<&str>,
@@ -300,4 +347,41 @@ directory contents
         assert!(!tool2.was_called());
         assert!(!tool3.was_called());
     }
+
+    #[test]
+    fn test_execute_tool_calls_answers_every_call() {
+        let tool1 = MockTool::new("tool1", None);
+        let tool2 = MockTool::new("tool2", Some("output".to_string()));
+
+        let calls = vec![
+            ToolCall {
+                id: "a".to_string(),
+                name: "tool1".to_string(),
+                arguments: json!({}),
+            },
+            ToolCall {
+                id: "b".to_string(),
+                name: "tool2".to_string(),
+                arguments: json!({}),
+            },
+            ToolCall {
+                id: "c".to_string(),
+                name: "missing".to_string(),
+                arguments: json!({}),
+            },
+        ];
+
+        let all_tools: Vec<Box<dyn Tool>> = vec![Box::new(tool1.clone()), Box::new(tool2.clone())];
+
+        let results = execute_tool_calls(&calls, all_tools).unwrap();
+
+        assert!(tool1.was_called());
+        assert!(tool2.was_called());
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|m| m.role == Role::Tool));
+        assert_eq!(results[0].tool_call_id.as_deref(), Some("a"));
+        assert_eq!(results[0].content, "Done.");
+        assert_eq!(results[1].content, "output");
+        assert!(results[2].content.contains("unknown tool"));
+    }
 }
@@ -1,7 +1,9 @@
 use clap::Parser;
 use std::error::Error;
+use std::time::Duration;
 
 use crate::llm_api::{ChatGPTClient, LLMClient, OllamaClient};
+use crate::tools::ToolConfig;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -17,6 +19,10 @@ pub struct Config {
     /// Port for Ollama server (ignored for OpenAI models)
     #[arg(long, default_value_t = 11434)]
     pub port: u16,
+
+    /// Seconds after which a command run by the shell tool is killed
+    #[arg(long, default_value_t = 120)]
+    pub shell_timeout: u64,
 }
 
 impl Config {
@@ -41,6 +47,12 @@ impl Config {
             _ => Err(format!("Unsupported provider: {}", provider).into()),
         }
     }
+
+    pub fn tool_config(&self) -> ToolConfig {
+        ToolConfig {
+            shell_timeout: Duration::from_secs(self.shell_timeout),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -54,6 +66,7 @@ mod tests {
         assert_eq!(config.model, "ollama/qwen2.5-coder");
         assert_eq!(config.hostname, "localhost");
         assert_eq!(config.port, 11434);
+        assert_eq!(config.shell_timeout, 120);
     }
 
     #[test]
@@ -66,10 +79,13 @@ mod tests {
             "api.example.com",
             "--port",
             "8080",
+            "--shell-timeout",
+            "600",
         ]);
         assert_eq!(config.model, "ollama/codellama");
         assert_eq!(config.hostname, "api.example.com");
         assert_eq!(config.port, 8080);
+        assert_eq!(config.tool_config().shell_timeout, Duration::from_secs(600));
     }
 
     #[test]
@@ -1,7 +1,9 @@
-use crate::llm_api::{LLMClient, Message, Role};
+use crate::llm_api::{read_lines, LLMClient, Message, OnDelta, Role, ToolCall};
+use crate::tools::get_function_schemas;
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, Response};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::env;
 use std::error::Error;
 use std::time::Duration;
@@ -16,14 +18,60 @@ pub struct ChatGPTClient {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct ChatGPTMessage {
     role: String,
-    content: String,
+    content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ChatGPTToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ChatGPTToolCall {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    function: ChatGPTFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ChatGPTFunctionCall {
+    name: String,
+    /// JSON encoded arguments object
+    arguments: String,
+}
+
+impl From<ChatGPTToolCall> for ToolCall {
+    fn from(call: ChatGPTToolCall) -> Self {
+        let arguments = serde_json::from_str(&call.function.arguments)
+            .unwrap_or(Value::String(call.function.arguments));
+        ToolCall {
+            id: call.id,
+            name: call.function.name,
+            arguments,
+        }
+    }
+}
+
+impl From<&ToolCall> for ChatGPTToolCall {
+    fn from(call: &ToolCall) -> Self {
+        ChatGPTToolCall {
+            id: call.id.clone(),
+            kind: "function".to_string(),
+            function: ChatGPTFunctionCall {
+                name: call.name.clone(),
+                arguments: call.arguments.to_string(),
+            },
+        }
+    }
 }
 
 impl From<ChatGPTMessage> for Message {
     fn from(msg: ChatGPTMessage) -> Self {
         Message {
             role: Role::from(msg.role.as_str()),
-            content: msg.content,
+            content: msg.content.unwrap_or_default(),
+            tool_calls: msg.tool_calls.into_iter().map(ToolCall::from).collect(),
+            tool_call_id: msg.tool_call_id,
         }
     }
 }
@@ -32,7 +80,9 @@ impl From<&Message> for ChatGPTMessage {
     fn from(msg: &Message) -> Self {
         ChatGPTMessage {
             role: msg.role.as_str().to_string(),
-            content: msg.content.clone(),
+            content: Some(msg.content.clone()),
+            tool_calls: msg.tool_calls.iter().map(ChatGPTToolCall::from).collect(),
+            tool_call_id: msg.tool_call_id.clone(),
         }
     }
 }
@@ -41,6 +91,10 @@ impl From<&Message> for ChatGPTMessage {
 struct ChatRequest {
     model: String,
     messages: Vec<ChatGPTMessage>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -53,6 +107,94 @@ struct Choice {
     message: ChatGPTMessage,
 }
 
+#[derive(Debug, Deserialize)]
+struct ChatGPTStreamChunk {
+    choices: Vec<StreamChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    delta: Delta,
+}
+
+#[derive(Debug, Deserialize)]
+struct Delta {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCallDelta>,
+}
+
+/// A piece of a tool call, the arguments arrive as string fragments
+#[derive(Debug, Deserialize)]
+struct ToolCallDelta {
+    index: usize,
+    id: Option<String>,
+    function: Option<FunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+/// Applies one line of the server-sent event stream to the message being
+/// assembled and returns the new piece of content, if any
+fn apply_stream_line(
+    line: &str,
+    message: &mut ChatGPTMessage,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let data = match line.strip_prefix("data:") {
+        Some(data) => data.trim(),
+        None => return Ok(None),
+    };
+    if data == "[DONE]" {
+        return Ok(None);
+    }
+
+    let chunk: ChatGPTStreamChunk = serde_json::from_str(data)?;
+    let delta = match chunk.choices.into_iter().next() {
+        Some(choice) => choice.delta,
+        None => return Ok(None),
+    };
+
+    for call in delta.tool_calls {
+        while message.tool_calls.len() <= call.index {
+            message.tool_calls.push(ChatGPTToolCall {
+                id: String::new(),
+                kind: "function".to_string(),
+                function: ChatGPTFunctionCall {
+                    name: String::new(),
+                    arguments: String::new(),
+                },
+            });
+        }
+        let target = &mut message.tool_calls[call.index];
+        if let Some(id) = call.id {
+            target.id = id;
+        }
+        if let Some(function) = call.function {
+            target
+                .function
+                .name
+                .push_str(&function.name.unwrap_or_default());
+            target
+                .function
+                .arguments
+                .push_str(&function.arguments.unwrap_or_default());
+        }
+    }
+
+    if let Some(content) = &delta.content {
+        message
+            .content
+            .get_or_insert_with(String::new)
+            .push_str(content);
+    }
+
+    Ok(delta.content)
+}
+
 impl ChatGPTClient {
     pub fn new(model: String) -> Result<Self, Box<dyn Error>> {
         let api_key = env::var("OPENAI_API_KEY")?;
@@ -74,28 +216,62 @@ impl ChatGPTClient {
         history: Vec<Message>,
         user_message: String,
     ) -> Result<Message, Box<dyn Error>> {
-        let url = "https://api.openai.com/v1/chat/completions";
+        let mut messages = history;
+        messages.push(Message::new(Role::User, user_message));
 
-        let mut messages = Vec::new();
+        self.chat_messages(system_prompt, messages).await
+    }
 
-        if let Some(system) = system_prompt {
-            messages.push(ChatGPTMessage {
-                role: Role::System.as_str().to_string(),
-                content: system,
-            });
-        }
+    pub async fn chat_messages(
+        &self,
+        system_prompt: Option<String>,
+        history: Vec<Message>,
+    ) -> Result<Message, Box<dyn Error>> {
+        let response = self.send(system_prompt, &history, false).await?;
+        let response = response.json::<ChatGPTResponse>().await?;
 
-        messages.extend(history.iter().map(ChatGPTMessage::from));
+        response
+            .choices
+            .first()
+            .map(|choice| choice.message.clone().into())
+            .ok_or_else(|| "No response from ChatGPT".into())
+    }
 
-        messages.push(ChatGPTMessage {
-            role: Role::User.as_str().to_string(),
-            content: user_message,
-        });
+    pub async fn chat_stream(
+        &self,
+        system_prompt: Option<String>,
+        history: Vec<Message>,
+        on_delta: &mut OnDelta<'_>,
+    ) -> Result<Message, Box<dyn Error>> {
+        let response = self.send(system_prompt, &history, true).await?;
 
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages,
+        let mut message = ChatGPTMessage {
+            role: Role::Agent.as_str().to_string(),
+            content: None,
+            tool_calls: vec![],
+            tool_call_id: None,
         };
+        read_lines(response, &mut |line| {
+            if let Some(content) = apply_stream_line(line, &mut message)? {
+                on_delta(&content);
+            }
+            Ok(())
+        })
+        .await?;
+
+        Ok(message.into())
+    }
+
+    async fn send(
+        &self,
+        system_prompt: Option<String>,
+        history: &[Message],
+        stream: bool,
+    ) -> Result<Response, Box<dyn Error>> {
+        let url = "https://api.openai.com/v1/chat/completions";
+
+        let mut request = self.build_request(system_prompt, history);
+        request.stream = stream;
 
         let response = self
             .client
@@ -103,15 +279,31 @@ impl ChatGPTClient {
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&request)
             .send()
-            .await?
-            .json::<ChatGPTResponse>()
             .await?;
 
-        response
-            .choices
-            .first()
-            .map(|choice| choice.message.clone().into())
-            .ok_or_else(|| "No response from ChatGPT".into())
+        Ok(response)
+    }
+
+    fn build_request(&self, system_prompt: Option<String>, history: &[Message]) -> ChatRequest {
+        let mut messages = Vec::new();
+
+        if let Some(system) = system_prompt {
+            messages.push(ChatGPTMessage::from(&Message::new(Role::System, system)));
+        }
+
+        messages.extend(history.iter().map(ChatGPTMessage::from));
+
+        let tools = get_function_schemas()
+            .into_iter()
+            .map(|function| json!({ "type": "function", "function": function }))
+            .collect();
+
+        ChatRequest {
+            model: self.model.clone(),
+            messages,
+            stream: false,
+            tools,
+        }
     }
 }
 
@@ -125,6 +317,27 @@ impl LLMClient for ChatGPTClient {
     ) -> Result<Message, Box<dyn Error>> {
         self.chat(system_prompt, history, user_message).await
     }
+
+    async fn chat_messages(
+        &self,
+        system_prompt: Option<String>,
+        messages: Vec<Message>,
+    ) -> Result<Message, Box<dyn Error>> {
+        self.chat_messages(system_prompt, messages).await
+    }
+
+    async fn chat_stream(
+        &self,
+        system_prompt: Option<String>,
+        messages: Vec<Message>,
+        on_delta: &mut OnDelta<'_>,
+    ) -> Result<Message, Box<dyn Error>> {
+        self.chat_stream(system_prompt, messages, on_delta).await
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -173,13 +386,7 @@ mod tests {
         let response2 = client
             .chat(
                 None,
-                vec![
-                    Message {
-                        role: Role::User,
-                        content: "What is Rust?".to_string(),
-                    },
-                    response1.clone(),
-                ],
+                vec![Message::new(Role::User, "What is Rust?"), response1.clone()],
                 "What are its main features?".to_string(),
             )
             .await
@@ -188,4 +395,102 @@ mod tests {
         assert!(!response1.content.is_empty());
         assert!(!response2.content.is_empty());
     }
+
+    fn client() -> ChatGPTClient {
+        ChatGPTClient {
+            api_key: "test-key".to_string(),
+            model: "gpt-4".to_string(),
+            client: Client::new(),
+        }
+    }
+
+    #[test]
+    fn test_request_advertises_tools() {
+        let request = client().build_request(None, &[Message::new(Role::User, "Hi")]);
+        let json = serde_json::to_value(&request).unwrap();
+
+        let tools = json["tools"].as_array().unwrap();
+        assert!(tools
+            .iter()
+            .any(|tool| tool["type"] == "function" && tool["function"]["name"] == "save"));
+    }
+
+    #[test]
+    fn test_tool_result_is_sent_with_call_id() {
+        let assistant = Message {
+            role: Role::Agent,
+            content: String::new(),
+            tool_calls: vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "save".to_string(),
+                arguments: json!({"path": "a.txt", "content": "A"}),
+            }],
+            tool_call_id: None,
+        };
+        let request = client().build_request(
+            Some("System".to_string()),
+            &[assistant, Message::tool_result("call_1", "Done")],
+        );
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["messages"][0]["role"], "system");
+        assert_eq!(json["messages"][1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(
+            json["messages"][1]["tool_calls"][0]["function"]["arguments"],
+            r#"{"content":"A","path":"a.txt"}"#
+        );
+        assert_eq!(json["messages"][2]["role"], "tool");
+        assert_eq!(json["messages"][2]["tool_call_id"], "call_1");
+    }
+
+    #[test]
+    fn test_parse_tool_call_response() {
+        let body = r#"{"choices":[{"message":{"role":"assistant","content":null,
+            "tool_calls":[{"id":"call_9","type":"function",
+            "function":{"name":"save","arguments":"{\"path\":\"x.rs\",\"content\":\"fn main() {}\"}"}}]}}]}"#;
+        let response: ChatGPTResponse = serde_json::from_str(body).unwrap();
+        let message: Message = response.choices[0].message.clone().into();
+
+        assert_eq!(message.role, Role::Agent);
+        assert_eq!(message.content, "");
+        assert_eq!(
+            message.tool_calls,
+            vec![ToolCall {
+                id: "call_9".to_string(),
+                name: "save".to_string(),
+                arguments: json!({"path": "x.rs", "content": "fn main() {}"}),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_assemble_streamed_message() {
+        let lines = [
+            r#"data: {"choices":[{"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            "",
+            r#"data: {"choices":[{"delta":{"content":"lo"}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"save","arguments":"{\"path\":"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"a.txt\"}"}}]}}]}"#,
+            "data: [DONE]",
+        ];
+
+        let mut message = ChatGPTMessage {
+            role: "assistant".to_string(),
+            content: None,
+            tool_calls: vec![],
+            tool_call_id: None,
+        };
+        let mut deltas = Vec::new();
+        for line in lines {
+            if let Some(delta) = apply_stream_line(line, &mut message).unwrap() {
+                deltas.push(delta);
+            }
+        }
+        let message: Message = message.into();
+
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        assert_eq!(message.content, "Hello");
+        assert_eq!(message.tool_calls[0].id, "call_1");
+        assert_eq!(message.tool_calls[0].arguments, json!({"path": "a.txt"}));
+    }
 }
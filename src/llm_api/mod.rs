@@ -6,6 +6,7 @@ pub enum Role {
     System,
     User,
     Agent,
+    Tool,
 }
 
 impl Role {
@@ -14,6 +15,7 @@ impl Role {
             Role::System => "system",
             Role::User => "user",
             Role::Agent => "assistant",
+            Role::Tool => "tool",
         }
     }
 }
@@ -29,17 +31,55 @@ impl From<&str> for Role {
         match s {
             "system" => Role::System,
             "user" => Role::User,
+            "tool" => Role::Tool,
             _ => Role::Agent,
         }
     }
 }
 
+/// A native function call requested by the model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: Role,
     pub content: String,
+    /// Function calls requested by the assistant (native tool calling only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// The call a `Role::Tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
 }
 
+impl Message {
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Message {
+            role,
+            content: content.into(),
+            tool_calls: vec![],
+            tool_call_id: None,
+        }
+    }
+
+    pub fn tool_result(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Message {
+            role: Role::Tool,
+            content: content.into(),
+            tool_calls: vec![],
+            tool_call_id: Some(tool_call_id.into()),
+        }
+    }
+}
+
+/// Receives the pieces of a streamed response
+pub type OnDelta<'a> = dyn FnMut(&str) + Send + 'a;
+
 // Define the trait for LLM clients
 #[async_trait::async_trait]
 pub trait LLMClient: Send + Sync {
@@ -49,6 +89,41 @@ pub trait LLMClient: Send + Sync {
         history: Vec<Message>,
         user_message: String,
     ) -> Result<Message, Box<dyn Error>>;
+
+    /// Sends a complete list of messages, the last one being the new input.
+    ///
+    /// Clients that support tool results override this, so that `Role::Tool`
+    /// messages are not flattened into a user message.
+    async fn chat_messages(
+        &self,
+        system_prompt: Option<String>,
+        mut messages: Vec<Message>,
+    ) -> Result<Message, Box<dyn Error>> {
+        let last = messages.pop().ok_or("No message to send")?;
+        self.chat(system_prompt, messages, last.content).await
+    }
+
+    /// Sends the messages like `chat_messages`, but hands every piece of the
+    /// response content to `on_delta` as soon as it arrives.
+    ///
+    /// Returns the assembled message. Clients without streaming support
+    /// deliver the whole content as a single delta.
+    async fn chat_stream(
+        &self,
+        system_prompt: Option<String>,
+        messages: Vec<Message>,
+        on_delta: &mut OnDelta<'_>,
+    ) -> Result<Message, Box<dyn Error>> {
+        let message = self.chat_messages(system_prompt, messages).await?;
+        on_delta(&message.content);
+        Ok(message)
+    }
+
+    /// Whether tools are advertised to the model through the provider's
+    /// function calling API instead of the fenced block prompt
+    fn supports_native_tools(&self) -> bool {
+        false
+    }
 }
 
 // Generic LLM API wrapper
@@ -73,6 +148,31 @@ impl LLMAPI {
     }
 }
 
+/// Handles one line of a streamed response body
+pub(crate) type OnLine<'a> = dyn FnMut(&str) -> Result<(), Box<dyn Error>> + Send + 'a;
+
+/// Reads a streamed response body line by line
+pub(crate) async fn read_lines(
+    mut response: reqwest::Response,
+    on_line: &mut OnLine<'_>,
+) -> Result<(), Box<dyn Error>> {
+    let mut buffer = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        buffer.extend_from_slice(&chunk);
+        while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            on_line(String::from_utf8_lossy(&line).trim())?;
+        }
+    }
+
+    if !buffer.is_empty() {
+        on_line(String::from_utf8_lossy(&buffer).trim())?;
+    }
+
+    Ok(())
+}
+
 pub mod chat_gpt;
 pub mod ollama;
 
@@ -97,15 +197,15 @@ mod tests {
             history: Vec<Message>,
             user_message: String,
         ) -> Result<Message, Box<dyn Error>> {
-            Ok(Message {
-                role: Role::Agent,
-                content: format!(
+            Ok(Message::new(
+                Role::Agent,
+                format!(
                     "Mock response to: {}. System prompt: {:?}, History length: {}",
                     user_message,
                     system_prompt,
                     history.len()
                 ),
-            })
+            ))
         }
     }
 
@@ -150,14 +250,8 @@ mod tests {
         let api = LLMAPI::new(mock_client);
 
         let history = vec![
-            Message {
-                role: Role::User,
-                content: "Previous message".to_string(),
-            },
-            Message {
-                role: Role::Agent,
-                content: "Previous response".to_string(),
-            },
+            Message::new(Role::User, "Previous message"),
+            Message::new(Role::Agent, "Previous response"),
         ];
 
         let response = api
@@ -186,13 +280,7 @@ mod tests {
             .unwrap();
 
         // Second message with history
-        let history = vec![
-            Message {
-                role: Role::User,
-                content: "First message".to_string(),
-            },
-            response1,
-        ];
+        let history = vec![Message::new(Role::User, "First message"), response1];
 
         let response2 = api
             .chat(None, history, "Second message".to_string())
@@ -202,4 +290,21 @@ mod tests {
         assert!(response2.content.contains("Second message"));
         assert!(response2.content.contains("History length: 2"));
     }
+
+    #[tokio::test]
+    async fn test_default_chat_stream_delivers_whole_content() {
+        let mock_client = MockLLMClient;
+        let mut deltas = Vec::new();
+
+        let response = mock_client
+            .chat_stream(
+                None,
+                vec![Message::new(Role::User, "Streamed message")],
+                &mut |delta| deltas.push(delta.to_string()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(deltas, vec![response.content]);
+    }
 }
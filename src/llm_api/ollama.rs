@@ -1,8 +1,12 @@
-use crate::llm_api::{LLMClient, Message, Role};
+use crate::llm_api::{read_lines, LLMClient, Message, OnDelta, Role, ToolCall};
+use crate::tools::{get_function_schemas, get_tool_prompt};
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, Response};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -11,20 +15,45 @@ pub struct OllamaClient {
     port: u16,
     model: String,
     client: Client,
+    /// Cleared once the model rejects the `tools` field
+    native_tools: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct OllamaMessage {
     role: String,
+    #[serde(default)]
     content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OllamaToolCall>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct OllamaFunctionCall {
+    name: String,
+    arguments: Value,
 }
 
 impl From<OllamaMessage> for Message {
     fn from(msg: OllamaMessage) -> Self {
-        Message {
-            role: Role::from(msg.role.as_str()),
-            content: msg.content,
-        }
+        let mut message = Message::new(Role::from(msg.role.as_str()), msg.content);
+        // Ollama does not assign ids to tool calls, so number them
+        message.tool_calls = msg
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(|(index, call)| ToolCall {
+                id: format!("call_{}", index),
+                name: call.function.name,
+                arguments: call.function.arguments,
+            })
+            .collect();
+        message
     }
 }
 
@@ -33,6 +62,16 @@ impl From<&Message> for OllamaMessage {
         OllamaMessage {
             role: msg.role.as_str().to_string(),
             content: msg.content.clone(),
+            tool_calls: msg
+                .tool_calls
+                .iter()
+                .map(|call| OllamaToolCall {
+                    function: OllamaFunctionCall {
+                        name: call.name.clone(),
+                        arguments: call.arguments.clone(),
+                    },
+                })
+                .collect(),
         }
     }
 }
@@ -42,6 +81,8 @@ struct ChatRequest {
     model: String,
     messages: Vec<OllamaMessage>,
     stream: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -61,6 +102,7 @@ impl OllamaClient {
             port,
             model,
             client,
+            native_tools: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -70,40 +112,127 @@ impl OllamaClient {
         history: Vec<Message>,
         user_message: String,
     ) -> Result<Message, Box<dyn Error>> {
+        let mut messages = history;
+        messages.push(Message::new(Role::User, user_message));
+
+        self.chat_messages(system_prompt, messages).await
+    }
+
+    pub async fn chat_messages(
+        &self,
+        system_prompt: Option<String>,
+        history: Vec<Message>,
+    ) -> Result<Message, Box<dyn Error>> {
+        let response = self.send(system_prompt, &history, false).await?;
+        let response = response.json::<ChatResponse>().await?;
+
+        Ok(response.message.into())
+    }
+
+    pub async fn chat_stream(
+        &self,
+        system_prompt: Option<String>,
+        history: Vec<Message>,
+        on_delta: &mut OnDelta<'_>,
+    ) -> Result<Message, Box<dyn Error>> {
+        let response = self.send(system_prompt, &history, true).await?;
+
+        // The body is one JSON object per line, each carrying a piece of the message
+        let mut message = Message::new(Role::Agent, "");
+        read_lines(response, &mut |line| {
+            if line.is_empty() {
+                return Ok(());
+            }
+            let chunk: Message = serde_json::from_str::<ChatResponse>(line)?.message.into();
+            on_delta(&chunk.content);
+            message.content.push_str(&chunk.content);
+            message.tool_calls.extend(chunk.tool_calls);
+            Ok(())
+        })
+        .await?;
+
+        // Tool calls may be spread over several chunks, number them again
+        for (index, call) in message.tool_calls.iter_mut().enumerate() {
+            call.id = format!("call_{}", index);
+        }
+
+        Ok(message)
+    }
+
+    /// Posts the chat request and falls back to fenced block tools if the
+    /// model rejects the `tools` field
+    async fn send(
+        &self,
+        system_prompt: Option<String>,
+        history: &[Message],
+        stream: bool,
+    ) -> Result<Response, Box<dyn Error>> {
         let url = format!("http://{}:{}/api/chat", self.host, self.port);
 
+        let native_tools = self.native_tools.load(Ordering::Relaxed);
+        let request = self.build_request(system_prompt.clone(), history, native_tools, stream);
+
+        let mut response = self.client.post(&url).json(&request).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await?;
+
+            if !(native_tools && body.contains("does not support tools")) {
+                return Err(format!("Ollama returned {}: {}", status, body).into());
+            }
+
+            // The fenced block tools have to be described in the system
+            // prompt from now on
+            self.native_tools.store(false, Ordering::Relaxed);
+            let system_prompt = Some(format!(
+                "{}{}",
+                system_prompt.unwrap_or_default(),
+                get_tool_prompt()
+            ));
+            let request = self.build_request(system_prompt, history, false, stream);
+            response = self.client.post(&url).json(&request).send().await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await?;
+                return Err(format!("Ollama returned {}: {}", status, body).into());
+            }
+        }
+
+        Ok(response)
+    }
+
+    fn build_request(
+        &self,
+        system_prompt: Option<String>,
+        history: &[Message],
+        native_tools: bool,
+        stream: bool,
+    ) -> ChatRequest {
         let mut messages = Vec::new();
 
         if let Some(system) = system_prompt {
-            messages.push(OllamaMessage {
-                role: Role::System.as_str().to_string(),
-                content: system,
-            });
+            messages.push(OllamaMessage::from(&Message::new(Role::System, system)));
         }
 
         messages.extend(history.iter().map(OllamaMessage::from));
 
-        messages.push(OllamaMessage {
-            role: Role::User.as_str().to_string(),
-            content: user_message,
-        });
+        let tools = if native_tools {
+            get_function_schemas()
+                .into_iter()
+                .map(|function| json!({ "type": "function", "function": function }))
+                .collect()
+        } else {
+            vec![]
+        };
 
-        let request = ChatRequest {
+        ChatRequest {
             model: self.model.clone(),
             messages,
-            stream: false,
-        };
-
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await?
-            .json::<ChatResponse>()
-            .await?;
-
-        Ok(response.message.into())
+            stream,
+            tools,
+        }
     }
 }
 
@@ -117,6 +246,27 @@ impl LLMClient for OllamaClient {
     ) -> Result<Message, Box<dyn Error>> {
         self.chat(system_prompt, history, user_message).await
     }
+
+    async fn chat_messages(
+        &self,
+        system_prompt: Option<String>,
+        messages: Vec<Message>,
+    ) -> Result<Message, Box<dyn Error>> {
+        self.chat_messages(system_prompt, messages).await
+    }
+
+    async fn chat_stream(
+        &self,
+        system_prompt: Option<String>,
+        messages: Vec<Message>,
+        on_delta: &mut OnDelta<'_>,
+    ) -> Result<Message, Box<dyn Error>> {
+        self.chat_stream(system_prompt, messages, on_delta).await
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.native_tools.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
@@ -165,13 +315,7 @@ mod tests {
         let response2 = client
             .chat(
                 None,
-                vec![
-                    Message {
-                        role: Role::User,
-                        content: "What is Rust?".to_string(),
-                    },
-                    response1.clone(),
-                ],
+                vec![Message::new(Role::User, "What is Rust?"), response1.clone()],
                 "What are its main features?".to_string(),
             )
             .await
@@ -180,4 +324,40 @@ mod tests {
         assert!(!response1.content.is_empty());
         assert!(!response2.content.is_empty());
     }
+
+    #[test]
+    fn test_request_advertises_tools() {
+        let client = OllamaClient::new("localhost".to_string(), 11434, "qwen2.5".to_string());
+        let request = client.build_request(None, &[Message::new(Role::User, "Hi")], true, false);
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert!(json["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|tool| tool["function"]["name"] == "save"));
+
+        let request = client.build_request(None, &[Message::new(Role::User, "Hi")], false, true);
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("tools").is_none());
+        assert_eq!(json["stream"], true);
+    }
+
+    #[test]
+    fn test_parse_tool_calls() {
+        let body = r#"{"model":"qwen2.5","message":{"role":"assistant","content":"",
+            "tool_calls":[{"function":{"name":"save","arguments":{"path":"a.txt","content":"A"}}}]},
+            "done":true}"#;
+        let response: ChatResponse = serde_json::from_str(body).unwrap();
+        let message: Message = response.message.into();
+
+        assert_eq!(
+            message.tool_calls,
+            vec![ToolCall {
+                id: "call_0".to_string(),
+                name: "save".to_string(),
+                arguments: json!({"path": "a.txt", "content": "A"}),
+            }]
+        );
+    }
 }
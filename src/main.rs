@@ -12,10 +12,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Using LLM Provider: {}", model_parts[0]);
     println!("Using Model: {}", model_parts[1]);
 
-    let mut system_prompt = get_system_prompt();
-    system_prompt.push_str(&maach_et::tools::get_tool_prompt());
+    let system_prompt = get_system_prompt();
 
-    let mut chat_loop = ChatLoop::new(llm_client, system_prompt);
+    let mut chat_loop =
+        ChatLoop::new(llm_client, system_prompt).with_tool_config(config.tool_config());
 
     chat_loop.run().await?;
 
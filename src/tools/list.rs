@@ -0,0 +1,295 @@
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+use super::{string_argument, Tool};
+use crate::chat::git;
+
+/// Depth used when the model does not ask for one
+const DEFAULT_DEPTH: usize = 3;
+/// Maximum number of lines in the tree
+const MAX_ENTRIES: usize = 1000;
+/// Directories that are never listed
+const SKIPPED_DIRS: [&str; 2] = [".git", "target"];
+
+pub struct ListTool;
+
+impl ListTool {
+    pub fn new() -> Self {
+        ListTool {}
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+    size: Option<u64>,
+}
+
+impl Node {
+    fn insert(&mut self, path: &str, size: u64) {
+        let mut node = self;
+        for part in path.split('/').filter(|part| !part.is_empty()) {
+            node = node.children.entry(part.to_string()).or_default();
+        }
+        node.size = Some(size);
+    }
+
+    fn file_count(&self) -> usize {
+        if self.size.is_some() {
+            1
+        } else {
+            self.children.values().map(Node::file_count).sum()
+        }
+    }
+
+    fn render(&self, depth: usize, max_depth: usize, lines: &mut Vec<String>) {
+        // Directories first, then files
+        let (dirs, files): (Vec<_>, Vec<_>) = self
+            .children
+            .iter()
+            .partition(|(_, child)| child.size.is_none());
+        let indent = "  ".repeat(depth);
+
+        for (name, child) in dirs {
+            if depth + 1 >= max_depth {
+                lines.push(format!(
+                    "{}{}/ ({} files)",
+                    indent,
+                    name,
+                    child.file_count()
+                ));
+            } else {
+                lines.push(format!("{}{}/", indent, name));
+                child.render(depth + 1, max_depth, lines);
+            }
+        }
+        for (name, child) in files {
+            lines.push(format!(
+                "{}{} ({})",
+                indent,
+                name,
+                format_size(child.size.unwrap_or(0))
+            ));
+        }
+    }
+}
+
+fn format_size(size: u64) -> String {
+    match size {
+        s if s < 1024 => format!("{} B", s),
+        s if s < 1024 * 1024 => format!("{:.1} KB", s as f64 / 1024.0),
+        s => format!("{:.1} MB", s as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+fn is_skipped(path: &str) -> bool {
+    path.split('/').any(|part| SKIPPED_DIRS.contains(&part))
+}
+
+/// Collects all files below `dir` without asking git, skipping the
+/// directories in `SKIPPED_DIRS`
+fn walk(dir: &Path, prefix: &str, files: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = format!("{}{}", prefix, name);
+        if is_skipped(&path) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), &format!("{}/", path), files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Splits `[path] [depth]` into the directory and the depth
+fn parse_parameter(parameter: Option<&str>) -> (String, usize) {
+    let parts: Vec<&str> = parameter.unwrap_or("").split_whitespace().collect();
+    match parts.as_slice() {
+        [] => (".".to_string(), DEFAULT_DEPTH),
+        [single] => match single.parse() {
+            Ok(depth) => (".".to_string(), depth),
+            Err(_) => (single.to_string(), DEFAULT_DEPTH),
+        },
+        [path @ .., last] => match last.parse() {
+            Ok(depth) => (path.join(" "), depth),
+            Err(_) => (parts.join(" "), DEFAULT_DEPTH),
+        },
+    }
+}
+
+impl Tool for ListTool {
+    fn get_description(&self) -> String {
+        let prompt = r#"
+## List Tool
+
+### Purpose:
+Show the files and directories of a directory as a tree, with file sizes.
+Files ignored by git, `.git/` and `target/` are left out.
+Use this tool to get an overview of the project before reading or changing files.
+
+### Usage Pattern:
+
+Use an empty code block with the language tag: `list [<path>] [<depth>]`
+The path defaults to the current directory, the depth to 3.
+
+Examples:
+
+```list
+```
+
+```list src 2
+```
+
+### Output:
+
+```
+Directory: src (depth 2)
+chat/ (4 files)
+main.rs (812 B)
+```
+
+        "#;
+
+        prompt.to_string()
+    }
+
+    fn get_indicator(&self) -> String {
+        "list".to_string()
+    }
+
+    fn get_summary(&self) -> String {
+        "List the files of a directory as a tree with file sizes, honouring .gitignore.".to_string()
+    }
+
+    fn get_parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory to list, defaults to the current directory"
+                },
+                "depth": {
+                    "type": "integer",
+                    "description": "How many directory levels to show, defaults to 3"
+                }
+            }
+        })
+    }
+
+    fn parse_arguments(&self, arguments: &Value) -> (Option<String>, String) {
+        let path = string_argument(arguments, "path").unwrap_or_else(|| ".".to_string());
+        let parameter = match arguments.get("depth").and_then(Value::as_u64) {
+            Some(depth) => format!("{} {}", path, depth),
+            None => path,
+        };
+
+        (Some(parameter), String::new())
+    }
+
+    fn execute(
+        &self,
+        parameter: Option<&str>,
+        _content: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let (path, depth) = parse_parameter(parameter);
+        let dir = Path::new(&path);
+        if !dir.is_dir() {
+            return Err(format!("{} is not a directory", path).into());
+        }
+
+        let files = match git::list_files(dir) {
+            Some(files) => files,
+            None => {
+                let mut files = Vec::new();
+                walk(dir, "", &mut files)?;
+                files
+            }
+        };
+
+        let mut root = Node::default();
+        for file in files.iter().filter(|file| !is_skipped(file)) {
+            // Deleted files are still listed by git until the change is staged
+            if let Ok(metadata) = std::fs::metadata(dir.join(file)) {
+                root.insert(file, metadata.len());
+            }
+        }
+
+        let mut lines = Vec::new();
+        root.render(0, depth.max(1), &mut lines);
+
+        let mut output = format!("Directory: {} (depth {})\n", path, depth.max(1));
+        for line in lines.iter().take(MAX_ENTRIES) {
+            output.push_str(line);
+            output.push('\n');
+        }
+        if lines.len() > MAX_ENTRIES {
+            output.push_str(&format!(
+                "... {} more entries, list a subdirectory or use a smaller depth\n",
+                lines.len() - MAX_ENTRIES
+            ));
+        }
+
+        Ok(Some(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_parameter() {
+        assert_eq!(parse_parameter(None), (".".to_string(), DEFAULT_DEPTH));
+        assert_eq!(
+            parse_parameter(Some("src")),
+            ("src".to_string(), DEFAULT_DEPTH)
+        );
+        assert_eq!(parse_parameter(Some("2")), (".".to_string(), 2));
+        assert_eq!(parse_parameter(Some("src 1")), ("src".to_string(), 1));
+    }
+
+    #[test]
+    fn test_render_tree_with_depth_limit() {
+        let mut root = Node::default();
+        root.insert("Cargo.toml", 120);
+        root.insert("src/main.rs", 2048);
+        root.insert("src/chat/mod.rs", 10);
+        root.insert("src/chat/git.rs", 10);
+
+        let mut lines = Vec::new();
+        root.render(0, 2, &mut lines);
+
+        assert_eq!(
+            lines,
+            vec![
+                "src/",
+                "  chat/ (2 files)",
+                "  main.rs (2.0 KB)",
+                "Cargo.toml (120 B)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skips_target_and_git() {
+        assert!(is_skipped("target/debug/maach_et"));
+        assert!(is_skipped(".git/HEAD"));
+        assert!(!is_skipped("src/target.rs"));
+    }
+
+    #[test]
+    fn test_list_source_directory() {
+        let output = ListTool::new().execute(Some("src 1"), "").unwrap().unwrap();
+
+        assert!(output.starts_with("Directory: src (depth 1)\n"));
+        assert!(output.contains("tools/ ("));
+        assert!(output.contains("main.rs ("));
+    }
+}
@@ -1,10 +1,23 @@
+use serde_json::{json, Value};
 use std::error::Error;
+use std::time::Duration;
 
+mod list;
+mod patch;
+mod read;
 mod save;
+mod shell;
 
 pub trait Tool: 'static {
     fn get_description(&self) -> String;
     fn get_indicator(&self) -> String;
+    /// One-line description used for native function calling
+    fn get_summary(&self) -> String;
+    /// JSON schema of the arguments object for native function calling
+    fn get_parameters(&self) -> Value;
+    /// Maps the arguments of a native function call onto the
+    /// `parameter` and `content` passed to `execute`
+    fn parse_arguments(&self, arguments: &Value) -> (Option<String>, String);
     fn execute(
         &self,
         parameter: Option<&str>,
@@ -12,8 +25,57 @@ pub trait Tool: 'static {
     ) -> Result<Option<String>, Box<dyn Error>>;
 }
 
+/// Settings of the tools that can be changed by the user
+#[derive(Debug, Clone)]
+pub struct ToolConfig {
+    /// Time after which a shell command is killed
+    pub shell_timeout: Duration,
+}
+
+impl Default for ToolConfig {
+    fn default() -> Self {
+        Self {
+            shell_timeout: Duration::from_secs(120),
+        }
+    }
+}
+
 pub fn get_all_tools() -> Vec<Box<dyn Tool>> {
-    vec![Box::new(save::SaveTool::new())]
+    get_configured_tools(&ToolConfig::default())
+}
+
+pub fn get_configured_tools(config: &ToolConfig) -> Vec<Box<dyn Tool>> {
+    vec![
+        Box::new(save::SaveTool::new()),
+        Box::new(read::ReadTool::new()),
+        Box::new(list::ListTool::new()),
+        Box::new(patch::PatchTool::new()),
+        Box::new(shell::ShellTool::new(config.shell_timeout)),
+    ]
+}
+
+/// Function descriptions of all tools in the `{name, description, parameters}`
+/// shape shared by the OpenAI and Ollama APIs
+pub fn get_function_schemas() -> Vec<Value> {
+    get_all_tools()
+        .iter()
+        .map(|tool| {
+            json!({
+                "name": tool.get_indicator(),
+                "description": tool.get_summary(),
+                "parameters": tool.get_parameters(),
+            })
+        })
+        .collect()
+}
+
+/// Reads a string argument of a native function call
+pub fn string_argument(arguments: &Value, key: &str) -> Option<String> {
+    arguments.get(key).and_then(|value| match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    })
 }
 
 pub fn get_tool_prompt() -> String {
@@ -0,0 +1,460 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::path::Path;
+
+use super::{string_argument, Tool};
+
+pub struct PatchTool;
+
+impl PatchTool {
+    pub fn new() -> Self {
+        PatchTool {}
+    }
+}
+
+/// One change to a file: the lines to find and the lines to put in their place
+#[derive(Debug, PartialEq)]
+struct Hunk {
+    /// How the hunk is referred to in error messages
+    label: String,
+    old: Vec<String>,
+    new: Vec<String>,
+    /// 1-based line where a unified diff hunk expects the old lines
+    line_hint: Option<usize>,
+}
+
+/// Parses `<<<<<<< SEARCH` / `=======` / `>>>>>>> REPLACE` blocks
+fn parse_search_replace(patch: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks = Vec::new();
+    let mut lines = patch.lines();
+
+    while let Some(line) = lines.next() {
+        if !line.trim_start().starts_with("<<<<<<<") {
+            continue;
+        }
+        let label = format!("search/replace block {}", hunks.len() + 1);
+
+        let mut old = Vec::new();
+        loop {
+            match lines.next() {
+                Some(line) if line.trim_start().starts_with("=======") => break,
+                Some(line) => old.push(line.to_string()),
+                None => return Err(format!("{} has no ======= separator", label)),
+            }
+        }
+        let mut new = Vec::new();
+        loop {
+            match lines.next() {
+                Some(line) if line.trim_start().starts_with(">>>>>>>") => break,
+                Some(line) => new.push(line.to_string()),
+                None => return Err(format!("{} has no >>>>>>> REPLACE end", label)),
+            }
+        }
+        if old.is_empty() {
+            return Err(format!("{} has an empty SEARCH part", label));
+        }
+
+        hunks.push(Hunk {
+            label,
+            old,
+            new,
+            line_hint: None,
+        });
+    }
+
+    Ok(hunks)
+}
+
+/// Parses the hunks of a unified diff, file headers are ignored
+fn parse_unified_diff(patch: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let lines: Vec<&str> = patch.lines().collect();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        index += 1;
+
+        if line.starts_with("--- ") && lines.get(index).is_some_and(|l| l.starts_with("+++ ")) {
+            index += 1;
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            // @@ -start,count +start,count @@
+            let line_hint = line
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('-'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse::<usize>().ok());
+            hunks.push(Hunk {
+                label: format!("hunk {} ({})", hunks.len() + 1, line.trim()),
+                old: vec![],
+                new: vec![],
+                line_hint,
+            });
+            continue;
+        }
+
+        let hunk = match hunks.last_mut() {
+            Some(hunk) => hunk,
+            None => continue,
+        };
+        if let Some(removed) = line.strip_prefix('-') {
+            hunk.old.push(removed.to_string());
+        } else if let Some(added) = line.strip_prefix('+') {
+            hunk.new.push(added.to_string());
+        } else if line.starts_with('\\') {
+            // "\ No newline at end of file"
+        } else {
+            let context = line.strip_prefix(' ').unwrap_or(line);
+            hunk.old.push(context.to_string());
+            hunk.new.push(context.to_string());
+        }
+    }
+
+    if hunks.is_empty() {
+        return Err("the unified diff contains no @@ hunk".to_string());
+    }
+
+    Ok(hunks)
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<Hunk>, String> {
+    if patch
+        .lines()
+        .any(|line| line.trim_start().starts_with("<<<<<<<"))
+    {
+        parse_search_replace(patch)
+    } else if patch.lines().any(|line| line.starts_with("@@")) {
+        parse_unified_diff(patch)
+    } else {
+        Err(
+            "expected a unified diff or <<<<<<< SEARCH / ======= / >>>>>>> REPLACE blocks"
+                .to_string(),
+        )
+    }
+}
+
+/// All positions from `from` on where `needle` matches `lines`
+fn find_all(lines: &[String], needle: &[String], from: usize, trim: bool) -> Vec<usize> {
+    if needle.len() > lines.len() {
+        return vec![];
+    }
+    (from..=lines.len() - needle.len())
+        .filter(|&start| {
+            needle.iter().enumerate().all(|(offset, line)| {
+                let candidate = &lines[start + offset];
+                if trim {
+                    candidate.trim_end() == line.trim_end()
+                } else {
+                    candidate == line
+                }
+            })
+        })
+        .collect()
+}
+
+/// Describes the place in the file that resembles the old lines of the hunk most
+fn nearest_match(lines: &[String], hunk: &Hunk) -> String {
+    let window = hunk.old.len().min(lines.len());
+    let best = (0..=lines.len() - window)
+        .map(|start| {
+            let score = hunk
+                .old
+                .iter()
+                .zip(&lines[start..start + window])
+                .filter(|(a, b)| a.trim() == b.trim())
+                .count();
+            (score, start)
+        })
+        .max_by_key(|(score, start)| (*score, std::cmp::Reverse(*start)));
+
+    match best {
+        Some((score, start)) if score > 0 => {
+            let mut text = format!(
+                "The nearest match starts at line {} ({} of {} lines equal):\n",
+                start + 1,
+                score,
+                hunk.old.len()
+            );
+            for (offset, line) in lines[start..start + window].iter().enumerate() {
+                let marker = match hunk.old.get(offset) {
+                    Some(expected) if expected.trim() == line.trim() => ' ',
+                    _ => '!',
+                };
+                text.push_str(&format!("{} {:>4}  {}\n", marker, start + offset + 1, line));
+            }
+            text
+        }
+        _ => "No line of it was found in the file.\n".to_string(),
+    }
+}
+
+/// Applies all hunks in order, or none of them
+fn apply_hunks(original: &str, hunks: &[Hunk]) -> Result<String, String> {
+    let mut lines: Vec<String> = original.lines().map(|line| line.to_string()).collect();
+    // Line numbers of a unified diff refer to the original file
+    let mut shift: isize = 0;
+    let mut cursor = 0;
+
+    for hunk in hunks {
+        let hint = hunk
+            .line_hint
+            .map(|line| (line as isize - 1 + shift).max(0) as usize);
+
+        if hunk.old.is_empty() {
+            let position = hint.unwrap_or(lines.len()).min(lines.len());
+            lines.splice(position..position, hunk.new.iter().cloned());
+            shift += hunk.new.len() as isize;
+            cursor = position + hunk.new.len();
+            continue;
+        }
+
+        let mut positions = find_all(&lines, &hunk.old, cursor, false);
+        if positions.is_empty() {
+            positions = find_all(&lines, &hunk.old, cursor, true);
+        }
+
+        let position = match (positions.as_slice(), hint) {
+            ([], _) => {
+                return Err(format!(
+                    "{} does not apply, its old lines were not found.\n{}",
+                    hunk.label,
+                    nearest_match(&lines, hunk)
+                ));
+            }
+            ([position], _) => *position,
+            (positions, Some(hint)) => *positions
+                .iter()
+                .min_by_key(|position| position.abs_diff(hint))
+                .unwrap(),
+            (positions, None) => {
+                let found: Vec<String> = positions.iter().map(|p| (p + 1).to_string()).collect();
+                return Err(format!(
+                    "{} is ambiguous, its old lines occur at lines {}. Add more context lines.",
+                    hunk.label,
+                    found.join(", ")
+                ));
+            }
+        };
+
+        lines.splice(
+            position..position + hunk.old.len(),
+            hunk.new.iter().cloned(),
+        );
+        shift += hunk.new.len() as isize - hunk.old.len() as isize;
+        cursor = position + hunk.new.len();
+    }
+
+    let mut patched = lines.join("\n");
+    if original.ends_with('\n') || original.is_empty() {
+        patched.push('\n');
+    }
+    Ok(patched)
+}
+
+/// Replaces the file by writing a sibling temporary file and renaming it
+fn write_atomically(path: &Path, content: &str) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.maach_et.tmp", file_name));
+
+    std::fs::write(&temp, content)?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        std::fs::set_permissions(&temp, metadata.permissions())?;
+    }
+    std::fs::rename(&temp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp);
+    })
+}
+
+impl Tool for PatchTool {
+    fn get_description(&self) -> String {
+        let prompt = r#"
+## Patch Tool
+
+### Purpose:
+Change parts of an existing file without rewriting all of it.
+Prefer this tool over the save tool for edits of existing files.
+Read the file first, so that the lines you want to change match exactly.
+
+### Usage Pattern:
+
+To patch a file, use a code block with the language tag: `patch <path>`
+The content is either one or more search/replace blocks, or a unified diff.
+
+Search/replace example:
+
+```patch src/main.rs
+<<<<<<< SEARCH
+    println!("Hello, world!");
+=======
+    println!("Hello, maach et!");
+>>>>>>> REPLACE
+```
+
+The SEARCH part must match the file exactly and only once. Include enough
+surrounding lines to make it unique.
+
+Unified diff example:
+
+```patch src/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!("Hello, world!");
++    println!("Hello, maach et!");
+ }
+```
+
+### Output:
+
+Either a confirmation, or a description of the part that did not apply
+together with the nearest matching lines of the file. In this case nothing
+has been changed, correct the patch and try again.
+
+        "#;
+
+        prompt.to_string()
+    }
+
+    fn get_indicator(&self) -> String {
+        "patch".to_string()
+    }
+
+    fn get_summary(&self) -> String {
+        "Edit an existing file with search/replace blocks or a unified diff. \
+         Either all changes are applied or none."
+            .to_string()
+    }
+
+    fn get_parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path of the file, relative to the current directory"
+                },
+                "patch": {
+                    "type": "string",
+                    "description": "A unified diff, or blocks of the form \
+                        <<<<<<< SEARCH\\n<old lines>\\n=======\\n<new lines>\\n>>>>>>> REPLACE"
+                }
+            },
+            "required": ["path", "patch"]
+        })
+    }
+
+    fn parse_arguments(&self, arguments: &Value) -> (Option<String>, String) {
+        (
+            string_argument(arguments, "path"),
+            string_argument(arguments, "patch").unwrap_or_default(),
+        )
+    }
+
+    fn execute(
+        &self,
+        parameter: Option<&str>,
+        content: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let path = parameter.ok_or_else(|| "No file path provided".to_string())?;
+        let original =
+            std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+
+        // A patch that does not apply is reported to the model, so it can correct it
+        let hunks = match parse_patch(content) {
+            Ok(hunks) => hunks,
+            Err(e) => return Ok(Some(format!("Patch for {} not applied: {}", path, e))),
+        };
+        let patched = match apply_hunks(&original, &hunks) {
+            Ok(patched) => patched,
+            Err(e) => {
+                return Ok(Some(format!(
+                    "Patch for {} not applied, the file is unchanged.\n{}",
+                    path, e
+                )))
+            }
+        };
+
+        write_atomically(Path::new(path), &patched)?;
+
+        Ok(Some(format!(
+            "Patch applied to {} ({} change{}).",
+            path,
+            hunks.len(),
+            if hunks.len() == 1 { "" } else { "s" }
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+
+    #[test]
+    fn test_search_replace() {
+        let patch = "<<<<<<< SEARCH\n    println!(\"Hello, world!\");\n=======\n    println!(\"Hi\");\n    println!(\"there\");\n>>>>>>> REPLACE";
+
+        let hunks = parse_patch(patch).unwrap();
+        let patched = apply_hunks(FILE, &hunks).unwrap();
+
+        assert_eq!(
+            patched,
+            "fn main() {\n    println!(\"Hi\");\n    println!(\"there\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let patch = "--- a/main.rs\n+++ b/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    println!(\"Hello, world!\");\n+    println!(\"Hi\");\n }";
+
+        let hunks = parse_patch(patch).unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].line_hint, Some(1));
+
+        let patched = apply_hunks(FILE, &hunks).unwrap();
+        assert_eq!(patched, "fn main() {\n    println!(\"Hi\");\n}\n");
+    }
+
+    #[test]
+    fn test_failing_hunk_reports_nearest_match() {
+        let patch = "<<<<<<< SEARCH\nfn main() {\n    println!(\"Hello, moon!\");\n=======\n>>>>>>> REPLACE";
+
+        let error = apply_hunks(FILE, &parse_patch(patch).unwrap()).unwrap_err();
+
+        assert!(error.contains("search/replace block 1 does not apply"));
+        assert!(error.contains("nearest match starts at line 1 (1 of 2 lines equal)"));
+        assert!(error.contains("!    2      println!(\"Hello, world!\");"));
+    }
+
+    #[test]
+    fn test_ambiguous_search_is_rejected() {
+        let patch = "<<<<<<< SEARCH\n}\n=======\n>>>>>>> REPLACE";
+
+        let error = apply_hunks("{\n}\n{\n}\n", &parse_patch(patch).unwrap()).unwrap_err();
+
+        assert!(error.contains("occur at lines 2, 4"));
+    }
+
+    #[test]
+    fn test_patch_is_atomic() {
+        let path = std::env::temp_dir().join("maach_et_patch_atomic.rs");
+        std::fs::write(&path, FILE).unwrap();
+        let patch = "<<<<<<< SEARCH\nfn main() {\n=======\nfn start() {\n>>>>>>> REPLACE\n\
+                     <<<<<<< SEARCH\nnot in the file\n=======\n>>>>>>> REPLACE";
+
+        let output = PatchTool::new()
+            .execute(Some(&path.display().to_string()), patch)
+            .unwrap()
+            .unwrap();
+
+        assert!(output.contains("not applied"));
+        assert!(output.contains("search/replace block 2"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), FILE);
+    }
+}
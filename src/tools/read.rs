@@ -0,0 +1,251 @@
+use serde_json::{json, Value};
+use std::error::Error;
+
+use super::{string_argument, Tool};
+
+/// Maximum number of lines returned by a single read
+const MAX_LINES: usize = 500;
+/// Maximum number of bytes returned by a single read
+const MAX_BYTES: usize = 50_000;
+
+pub struct ReadTool;
+
+impl ReadTool {
+    pub fn new() -> Self {
+        ReadTool {}
+    }
+}
+
+/// Splits `path[:start-end]` into the path and the 1-based, inclusive line range
+fn parse_parameter(parameter: &str) -> (&str, usize, Option<usize>) {
+    if let Some((path, range)) = parameter.rsplit_once(':') {
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (start.trim(), Some(end.trim())),
+            None => (range.trim(), None),
+        };
+        if let Ok(start) = start.parse::<usize>() {
+            let end = match end {
+                None => Some(Ok(start)),
+                Some("") => None,
+                Some(end) => Some(end.parse::<usize>()),
+            };
+            match end {
+                None => return (path, start.max(1), None),
+                Some(Ok(end)) => return (path, start.max(1), Some(end)),
+                Some(Err(_)) => {}
+            }
+        }
+    }
+    (parameter, 1, None)
+}
+
+impl Tool for ReadTool {
+    fn get_description(&self) -> String {
+        let prompt = r#"
+## Read Tool
+
+### Purpose:
+Read the content of a file. Each line is prefixed with its line number.
+Always read a file before changing it.
+
+### Usage Pattern:
+
+The path can be relative to the current directory, or absolute.
+Optionally append a line range to read only a part of the file.
+
+To read a file, use an empty code block with the language tag: `read <path>[:start-end]`
+
+Examples:
+
+```read src/main.rs
+```
+
+```read src/main.rs:10-40
+```
+
+### Output:
+
+The file content with line numbers, like
+
+```
+File: src/main.rs (lines 1-3 of 3)
+   1  fn main() {
+   2      println!("Hello, world!");
+   3  }
+```
+
+Large files are truncated. Use a line range to read the rest.
+
+        "#;
+
+        prompt.to_string()
+    }
+
+    fn get_indicator(&self) -> String {
+        "read".to_string()
+    }
+
+    fn get_summary(&self) -> String {
+        "Read the content of a file with line numbers, optionally only a range of lines."
+            .to_string()
+    }
+
+    fn get_parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path of the file, relative to the current directory"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line to read, starting at 1"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line to read (inclusive)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn parse_arguments(&self, arguments: &Value) -> (Option<String>, String) {
+        let path = string_argument(arguments, "path");
+        let start = arguments.get("start_line").and_then(Value::as_u64);
+        let end = arguments.get("end_line").and_then(Value::as_u64);
+
+        let parameter = match (path, start, end) {
+            (Some(path), Some(start), Some(end)) => Some(format!("{}:{}-{}", path, start, end)),
+            (Some(path), Some(start), None) => Some(format!("{}:{}-", path, start)),
+            (Some(path), None, Some(end)) => Some(format!("{}:1-{}", path, end)),
+            (path, _, _) => path,
+        };
+
+        (parameter, String::new())
+    }
+
+    fn execute(
+        &self,
+        parameter: Option<&str>,
+        _content: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let parameter = parameter.ok_or_else(|| "No file path provided".to_string())?;
+        let (path, start, end) = parse_parameter(parameter);
+
+        let bytes = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        if bytes.iter().take(8000).any(|b| *b == 0) {
+            return Err(format!("{} is a binary file and cannot be read", path).into());
+        }
+        let text = String::from_utf8(bytes)
+            .map_err(|_| format!("{} is not a UTF-8 text file and cannot be read", path))?;
+
+        let lines: Vec<&str> = text.lines().collect();
+        let total = lines.len();
+        let end = end.unwrap_or(total).min(total);
+
+        if total > 0 && start > end {
+            return Err(format!(
+                "Invalid line range {}-{} for {} ({} lines)",
+                start, end, path, total
+            )
+            .into());
+        }
+
+        let mut body = String::new();
+        let mut last = start.saturating_sub(1);
+        for (index, line) in lines.iter().enumerate().take(end).skip(start - 1) {
+            let numbered = format!("{:>4}  {}\n", index + 1, line);
+            if last + 1 - start >= MAX_LINES || body.len() + numbered.len() > MAX_BYTES {
+                break;
+            }
+            body.push_str(&numbered);
+            last = index + 1;
+        }
+
+        let mut output = if total == 0 {
+            format!("File: {} (empty)\n", path)
+        } else {
+            format!(
+                "File: {} (lines {}-{} of {})\n{}",
+                path, start, last, total, body
+            )
+        };
+        if last < end {
+            output.push_str(&format!(
+                "... truncated, read {}:{}-{} for the remaining lines\n",
+                path,
+                last + 1,
+                end
+            ));
+        }
+
+        Ok(Some(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("maach_et_read_{}", name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_parameter() {
+        assert_eq!(parse_parameter("src/main.rs"), ("src/main.rs", 1, None));
+        assert_eq!(parse_parameter("a.rs:3-7"), ("a.rs", 3, Some(7)));
+        assert_eq!(parse_parameter("a.rs:3-"), ("a.rs", 3, None));
+        assert_eq!(parse_parameter("a.rs:5"), ("a.rs", 5, Some(5)));
+        assert_eq!(parse_parameter("C:/a.rs"), ("C:/a.rs", 1, None));
+    }
+
+    #[test]
+    fn test_read_with_line_numbers_and_range() {
+        let path = temp_file("range.txt", b"one\ntwo\nthree\nfour\n");
+        let tool = ReadTool::new();
+
+        let output = tool
+            .execute(Some(&format!("{}:2-3", path.display())), "")
+            .unwrap()
+            .unwrap();
+
+        assert!(output.contains("(lines 2-3 of 4)"));
+        assert!(output.contains("   2  two\n   3  three\n"));
+        assert!(!output.contains("four"));
+    }
+
+    #[test]
+    fn test_read_truncates_large_files() {
+        let content: String = (1..=MAX_LINES + 10).map(|n| format!("{}\n", n)).collect();
+        let path = temp_file("large.txt", content.as_bytes());
+
+        let output = ReadTool::new()
+            .execute(Some(&path.display().to_string()), "")
+            .unwrap()
+            .unwrap();
+
+        assert!(output.contains(&format!("(lines 1-{} of {})", MAX_LINES, MAX_LINES + 10)));
+        assert!(output.contains(&format!(
+            ":{}-{} for the remaining",
+            MAX_LINES + 1,
+            MAX_LINES + 10
+        )));
+    }
+
+    #[test]
+    fn test_read_rejects_binary_files() {
+        let path = temp_file("binary.bin", &[0x7f, b'E', b'L', b'F', 0, 1, 2]);
+
+        let error = ReadTool::new()
+            .execute(Some(&path.display().to_string()), "")
+            .unwrap_err();
+
+        assert!(error.to_string().contains("binary file"));
+    }
+}
@@ -1,6 +1,7 @@
+use serde_json::{json, Value};
 use std::error::Error;
 
-use super::Tool;
+use super::{string_argument, Tool};
 
 pub struct SaveTool;
 
@@ -48,6 +49,34 @@ no output
         "save".to_string()
     }
 
+    fn get_summary(&self) -> String {
+        "Create or overwrite a file with the given content.".to_string()
+    }
+
+    fn get_parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path of the file, relative to the current directory"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "The complete new content of the file"
+                }
+            },
+            "required": ["path", "content"]
+        })
+    }
+
+    fn parse_arguments(&self, arguments: &Value) -> (Option<String>, String) {
+        (
+            string_argument(arguments, "path"),
+            string_argument(arguments, "content").unwrap_or_default(),
+        )
+    }
+
     fn execute(
         &self,
         parameter: Option<&str>,
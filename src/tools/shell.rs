@@ -0,0 +1,273 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{string_argument, Tool};
+
+/// Output beyond this many bytes per stream is cut out of the middle
+const MAX_OUTPUT: usize = 10_000;
+
+pub struct ShellTool {
+    timeout: Duration,
+}
+
+impl ShellTool {
+    pub fn new(timeout: Duration) -> Self {
+        ShellTool { timeout }
+    }
+}
+
+/// Keeps the beginning and the end of long output, where the interesting
+/// parts (the command's first reaction and the final errors) usually are
+fn truncate_output(output: &str) -> String {
+    if output.len() <= MAX_OUTPUT {
+        return output.to_string();
+    }
+
+    let mut head_end = MAX_OUTPUT * 2 / 5;
+    while !output.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    let mut tail_start = output.len() - (MAX_OUTPUT - head_end);
+    while !output.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+
+    format!(
+        "{}\n... {} bytes omitted ...\n{}",
+        &output[..head_end],
+        tail_start - head_end,
+        &output[tail_start..]
+    )
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        // Own process group, so that a timeout also kills the children
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+        shell
+    }
+}
+
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .args(["-9", &format!("-{}", child.id())])
+        .status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn read_in_background<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = reader.read_to_end(&mut buffer);
+        String::from_utf8_lossy(&buffer).to_string()
+    })
+}
+
+impl Tool for ShellTool {
+    fn get_description(&self) -> String {
+        let prompt = format!(
+            r#"
+## Shell Tool
+
+### Purpose:
+Run a command in the shell, in the current directory. Use it to build the
+project, run tests, search files or inspect the environment.
+
+### Usage Pattern:
+
+Use a code block with the language tag: `shell [<timeout in seconds>]`
+The content is the command. The default timeout is {} seconds, after that the
+command is killed. Do not start interactive programs, they will not get input.
+
+Example:
+
+```shell
+cargo test
+```
+
+### Output:
+
+The exit status and the captured stdout and stderr. Long output is shortened
+in the middle.
+
+```
+$ cargo test
+Exit status: 101
+--- stdout ---
+...
+--- stderr ---
+...
+```
+
+        "#,
+            self.timeout.as_secs()
+        );
+
+        prompt
+    }
+
+    fn get_indicator(&self) -> String {
+        "shell".to_string()
+    }
+
+    fn get_summary(&self) -> String {
+        "Run a shell command in the current directory and return its exit status, stdout and stderr."
+            .to_string()
+    }
+
+    fn get_parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "The command to run"
+                },
+                "timeout": {
+                    "type": "integer",
+                    "description": format!(
+                        "Seconds after which the command is killed, defaults to {}",
+                        self.timeout.as_secs()
+                    )
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn parse_arguments(&self, arguments: &Value) -> (Option<String>, String) {
+        (
+            arguments
+                .get("timeout")
+                .and_then(Value::as_u64)
+                .map(|timeout| timeout.to_string()),
+            string_argument(arguments, "command").unwrap_or_default(),
+        )
+    }
+
+    fn execute(
+        &self,
+        parameter: Option<&str>,
+        content: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let command = content.trim();
+        if command.is_empty() {
+            return Err("No command provided".into());
+        }
+        let timeout = match parameter {
+            Some(seconds) => Duration::from_secs(
+                seconds
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid timeout: {}", seconds))?,
+            ),
+            None => self.timeout,
+        };
+
+        let mut child = shell_command(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Cannot run `{}`: {}", command, e))?;
+
+        // Read both pipes while waiting, a full pipe would block the command
+        let stdout = read_in_background(child.stdout.take().expect("stdout is piped"));
+        let stderr = read_in_background(child.stderr.take().expect("stderr is piped"));
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+            if started.elapsed() >= timeout {
+                kill(&mut child);
+                break None;
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        let mut output = format!("$ {}\n", command);
+        match status {
+            Some(status) => match status.code() {
+                Some(code) => output.push_str(&format!("Exit status: {}\n", code)),
+                None => output.push_str("Exit status: terminated by signal\n"),
+            },
+            None => output.push_str(&format!(
+                "Timed out after {} seconds, the command was killed\n",
+                timeout.as_secs()
+            )),
+        }
+        if !stdout.is_empty() {
+            output.push_str("--- stdout ---\n");
+            output.push_str(&truncate_output(&stdout));
+            if !output.ends_with('\n') {
+                output.push('\n');
+            }
+        }
+        if !stderr.is_empty() {
+            output.push_str("--- stderr ---\n");
+            output.push_str(&truncate_output(&stderr));
+            if !output.ends_with('\n') {
+                output.push('\n');
+            }
+        }
+
+        Ok(Some(output))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captures_output_and_exit_status() {
+        let tool = ShellTool::new(Duration::from_secs(10));
+
+        let output = tool
+            .execute(None, "echo out; echo err >&2; exit 3")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            output,
+            "$ echo out; echo err >&2; exit 3\nExit status: 3\n--- stdout ---\nout\n--- stderr ---\nerr\n"
+        );
+    }
+
+    #[test]
+    fn test_kills_command_after_timeout() {
+        let tool = ShellTool::new(Duration::from_secs(10));
+
+        let started = Instant::now();
+        let output = tool.execute(Some("1"), "sleep 5").unwrap().unwrap();
+
+        assert!(output.contains("Timed out after 1 seconds"));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_truncates_long_output() {
+        let output = truncate_output(&"x".repeat(MAX_OUTPUT * 3));
+
+        assert!(output.len() < MAX_OUTPUT + 100);
+        assert!(output.contains(&format!("... {} bytes omitted ...", MAX_OUTPUT * 2)));
+    }
+}